@@ -3,18 +3,20 @@ use std::{ffi::CStr, os::raw::c_uint};
 
 use aflpp_custom_mutator_sys::afl_state;
 
-use crate::{CustomMutator, FuzzResult};
+use crate::{FuzzResult, RawCustomMutator};
 
 #[allow(unused_variables)]
-/// A custom mutator that can fail. This mirrors [`CustomMutator`], but all methods return a [`Result<T, E>`] instead of `T`.
-/// This trait can be implemented as an alternative to [`CustomMutator`], when it is more convenient (specifically: methods of your mutator can use `?` for ergonomic error handling).
+/// A custom mutator that can fail. This mirrors [`RawCustomMutator`], but all methods return a [`Result<T, E>`] instead of `T`.
+/// This trait can be implemented as an alternative to [`RawCustomMutator`], when it is more convenient (specifically: methods of your mutator can use `?` for ergonomic error handling).
+/// Export with `export_mutator!(raw MyMutator)`, since this trait mirrors the `&CStr`-based raw API rather than the ergonomic one.
 /// [`FallibleCustomMutator::handle_err`] will be called in case any method returns an [`Result::Err`].
 pub trait FallibleCustomMutator {
     /// The error type. All methods must return the same error type.
     type TErr;
 
     /// The method which handles errors. It is convenient to log the error here.
-    /// This method is *expected to [panic!]*.
+    /// This method is *expected to [panic!]*. The panic is caught at the FFI boundary (see the `wrappers` module),
+    /// so this is the place to deliberately fail fast rather than something callers need to guard against themselves.
     fn handle_err(err: Self::TErr);
 
     fn init(afl: &'static afl_state, seed: c_uint) -> Result<Self, Self::TErr>
@@ -51,9 +53,44 @@ pub trait FallibleCustomMutator {
     fn introspection(&mut self) -> Result<Option<&CStr>, Self::TErr> {
         Ok(None)
     }
+
+    /// Initializes trimming for the given `buffer` and returns the number of trim steps AFL++ should attempt.
+    /// Returning `0` (the default) tells AFL++ that this mutator does not support trimming.
+    /// As with [`RawCustomMutator::init_trim`], overriding this only takes effect once you export with
+    /// `export_mutator!(raw MyMutator, trim)`.
+    fn init_trim(&mut self, buffer: &[u8]) -> Result<u32, Self::TErr> {
+        Ok(0)
+    }
+
+    /// Returns the current trimmed candidate buffer. Only called after [`FallibleCustomMutator::init_trim`] returned a non-zero step count.
+    fn trim(&mut self) -> Result<&[u8], Self::TErr> {
+        unreachable!("trim() called without any steps requested from init_trim()")
+    }
+
+    /// Called after AFL++ ran the candidate returned by [`FallibleCustomMutator::trim`]. `success` indicates whether the trimmed candidate preserved coverage.
+    /// Returns the index of the next trim step.
+    fn post_trim(&mut self, success: bool) -> Result<u32, Self::TErr> {
+        Ok(0)
+    }
+
+    fn post_process<'s>(&'s mut self, buffer: &'s [u8]) -> Result<&'s [u8], Self::TErr> {
+        Ok(buffer)
+    }
+
+    fn havoc_mutation(
+        &mut self,
+        buffer: &mut [u8],
+        max_size: usize,
+    ) -> Result<FuzzResult, Self::TErr> {
+        unreachable!("havoc_mutation() called despite havoc_mutation_probability() returning 0")
+    }
+
+    fn havoc_mutation_probability(&mut self) -> Result<u8, Self::TErr> {
+        Ok(0)
+    }
 }
 
-impl<M> CustomMutator for M
+impl<M> RawCustomMutator for M
 where
     M: FallibleCustomMutator,
     M::TErr: core::fmt::Debug,
@@ -135,4 +172,64 @@ where
             }
         }
     }
+
+    fn init_trim(&mut self, buffer: &[u8]) -> u32 {
+        match self.init_trim(buffer) {
+            Ok(r) => r,
+            Err(e) => {
+                Self::handle_err(e);
+                panic!("Error handler did not panic")
+            }
+        }
+    }
+
+    fn trim(&mut self) -> &[u8] {
+        match self.trim() {
+            Ok(r) => r,
+            Err(e) => {
+                Self::handle_err(e);
+                panic!("Error handler did not panic")
+            }
+        }
+    }
+
+    fn post_trim(&mut self, success: bool) -> u32 {
+        match self.post_trim(success) {
+            Ok(r) => r,
+            Err(e) => {
+                Self::handle_err(e);
+                panic!("Error handler did not panic")
+            }
+        }
+    }
+
+    fn post_process<'r>(&'r mut self, buffer: &'r [u8]) -> &'r [u8] {
+        match self.post_process(buffer) {
+            Ok(r) => r,
+            Err(e) => {
+                Self::handle_err(e);
+                panic!("Error handler did not panic")
+            }
+        }
+    }
+
+    fn havoc_mutation(&mut self, buffer: &mut [u8], max_size: usize) -> FuzzResult {
+        match self.havoc_mutation(buffer, max_size) {
+            Ok(r) => r,
+            Err(e) => {
+                Self::handle_err(e);
+                panic!("Error handler did not panic")
+            }
+        }
+    }
+
+    fn havoc_mutation_probability(&mut self) -> u8 {
+        match self.havoc_mutation_probability() {
+            Ok(r) => r,
+            Err(e) => {
+                Self::handle_err(e);
+                panic!("Error handler did not panic")
+            }
+        }
+    }
 }
@@ -0,0 +1,79 @@
+//! An adapter for driving a structured/grammar-based input generator (eg a `lain`-style generator) as a [`CustomMutator`].
+
+use std::os::raw::c_uint;
+
+use aflpp_custom_mutator_sys::afl_state;
+
+use crate::{CustomMutator, FuzzResult};
+
+/// A source of structured inputs, driven by a seeded RNG rather than byte-level mutation of the raw testcase buffer.
+/// Implement this instead of [`CustomMutator`] directly when inputs come from a grammar/structure generator
+/// (eg `lain`), and drive it via [`GenerativeMutator`].
+pub trait Generator {
+    /// Creates a fresh input from scratch, seeded by `rng_seed`. The result must be no longer than `max_size`.
+    fn new_input(&mut self, rng_seed: u32, max_size: usize) -> Vec<u8>;
+
+    /// Mutates `input` (the current testcase) into a new candidate, no longer than `max_size`.
+    fn mutate(&mut self, input: &[u8], max_size: usize) -> Vec<u8>;
+
+    /// How many generated candidates [`GenerativeMutator`] should report to AFL++ per input, via `fuzz_count`.
+    /// Returning more than the default of `1` makes AFL++ call `fuzz` that many times per queue entry.
+    fn candidates_per_input(&self) -> u32 {
+        1
+    }
+}
+
+/// A small, deterministic PRNG (SplitMix64) used to derive per-call seeds for the wrapped [`Generator`].
+/// Not cryptographically secure; it only needs to make generated inputs reproducible across runs with the same AFL++ seed.
+struct DeterministicRng(u64);
+
+impl DeterministicRng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        ((z ^ (z >> 31)) >> 32) as u32
+    }
+}
+
+/// Bridges a [`Generator`] into a [`CustomMutator`], owning the [`Vec<u8>`] it produces so [`CustomMutator::fuzz`]
+/// can hand it back to AFL++ as a [`FuzzResult::NewBuffer`] without the caller having to re-implement the unsafe
+/// buffer-lifetime dance themselves.
+pub struct GenerativeMutator<G> {
+    generator: G,
+    rng: DeterministicRng,
+    buf: Vec<u8>,
+}
+
+impl<G: Generator + Default> CustomMutator for GenerativeMutator<G> {
+    fn init(_afl: &'static afl_state, seed: c_uint) -> Self {
+        Self {
+            generator: G::default(),
+            rng: DeterministicRng::new(seed as u64),
+            buf: Vec::new(),
+        }
+    }
+
+    fn fuzz(&mut self, buffer: &mut [u8], _add_buff: Option<&[u8]>, max_size: usize) -> FuzzResult {
+        let rng_seed = self.rng.next_u32();
+        let generated = if buffer.is_empty() {
+            self.generator.new_input(rng_seed, max_size)
+        } else {
+            self.generator.mutate(buffer, max_size)
+        };
+        if generated.is_empty() || generated.len() > max_size {
+            return FuzzResult::Fail;
+        }
+        self.buf = generated;
+        FuzzResult::NewBuffer(&self.buf)
+    }
+
+    fn fuzz_count(&mut self, _buffer: &[u8]) -> u32 {
+        self.generator.candidates_per_input()
+    }
+}
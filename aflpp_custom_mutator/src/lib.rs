@@ -16,8 +16,9 @@
 //! See [`export_mutator`] for an example.
 //! 
 pub mod fallible;
+pub mod generative;
 
-use std::{ffi::CStr, os::raw::c_uint};
+use std::{ffi::CStr, os::raw::c_uint, path::Path};
 
 #[doc(hidden)]
 pub use aflpp_custom_mutator_sys::afl_state;
@@ -37,6 +38,10 @@ pub enum FuzzResult<'l> {
 #[allow(unused_variables)]
 /// Implement this trait for the mutator and export it using [`export_mutator`] to generate a custom mutator.
 /// For documentation refer to the AFL++ sources.
+/// This is the ergonomic, default trait to implement: `describe`/`introspection` return a plain `&str` and
+/// `queue_new_entry`/`queue_get` receive a `&Path`, instead of the hand-built `&CStr`s that the raw AFL++ API deals in.
+/// Filenames are passed through byte-for-byte, so paths that aren't valid UTF-8 work here too.
+/// If you need the zero-copy `&CStr` API instead (eg to return a description without it being copied into a `CString` on every call), implement [`RawCustomMutator`] directly.
 pub trait CustomMutator {
     fn init(afl: &'static afl_state, seed: c_uint) -> Self
     where
@@ -48,6 +53,77 @@ pub trait CustomMutator {
         1
     }
 
+    fn queue_new_entry(&mut self, filename_new_queue: &Path, _filename_orig_queue: Option<&Path>) {}
+
+    fn queue_get(&mut self, filename: &Path) -> bool {
+        true
+    }
+
+    fn describe(&mut self, max_description: usize) -> Option<&str> {
+        None
+    }
+
+    fn introspection(&mut self) -> Option<&str> {
+        None
+    }
+
+    /// Initializes trimming for the given `buffer` and returns the number of trim steps AFL++ should attempt.
+    /// AFL++ will then alternate calls to [`CustomMutator::trim`] and [`CustomMutator::post_trim`] that many times at most.
+    /// Returning `0` (the default) tells AFL++ that this mutator does not support trimming.
+    /// Overriding this only takes effect once you export with `export_mutator!(MyMutator, trim)`: AFL++ decides
+    /// whether to use its own built-in trimming based on whether the `afl_custom_trim` symbol is *present* at all,
+    /// so [`export_mutator`] only emits the trim symbols when explicitly asked to.
+    fn init_trim(&mut self, buffer: &[u8]) -> u32 {
+        0
+    }
+
+    /// Returns the current trimmed candidate buffer. Only called after [`CustomMutator::init_trim`] returned a non-zero step count.
+    fn trim(&mut self) -> &[u8] {
+        unreachable!("trim() called without any steps requested from init_trim()")
+    }
+
+    /// Called after AFL++ ran the candidate returned by [`CustomMutator::trim`]. `success` indicates whether the trimmed candidate preserved coverage.
+    /// Returns the index of the next trim step.
+    fn post_trim(&mut self, success: bool) -> u32 {
+        0
+    }
+
+    /// Transforms `buffer` into the byte stream that is actually written to the target, right before execution.
+    /// This allows a mutator to keep an internal representation (eg a structured/AST form) during [`CustomMutator::fuzz`] and only serialize it here.
+    /// The default implementation passes the buffer through unchanged.
+    fn post_process<'s>(&'s mut self, buffer: &'s [u8]) -> &'s [u8] {
+        buffer
+    }
+
+    /// Injects a single custom mutation into AFL++'s havoc stage. Only called when [`CustomMutator::havoc_mutation_probability`] returns non-zero.
+    fn havoc_mutation(&mut self, buffer: &mut [u8], max_size: usize) -> FuzzResult {
+        unreachable!("havoc_mutation() called despite havoc_mutation_probability() returning 0")
+    }
+
+    /// The probability (0-100) that AFL++ calls [`CustomMutator::havoc_mutation`] instead of one of its own havoc mutations.
+    /// Returning `0` (the default) means `havoc_mutation` is never called.
+    fn havoc_mutation_probability(&mut self) -> u8 {
+        0
+    }
+}
+
+#[allow(unused_variables)]
+/// The lower-level counterpart of [`CustomMutator`], mirroring the raw AFL++ custom mutator C API one-to-one:
+/// `describe`/`introspection` return a `&CStr` and `queue_new_entry`/`queue_get` receive a `&CStr`.
+/// Implement this instead of [`CustomMutator`] when you need to avoid the `&str`/`&Path` conversion
+/// (eg the per-call `CString` copy of `describe`/`introspection`) or want to hand-build the `CStr`s yourself.
+/// Export with `export_mutator!(raw MyMutator)` rather than the plain [`export_mutator`] form.
+pub trait RawCustomMutator {
+    fn init(afl: &'static afl_state, seed: c_uint) -> Self
+    where
+        Self: Sized;
+
+    fn fuzz(&mut self, buffer: &mut [u8], add_buff: Option<&[u8]>, max_size: usize) -> FuzzResult;
+
+    fn fuzz_count(&mut self, buffer: &[u8]) -> u32 {
+        1
+    }
+
     fn queue_new_entry(&mut self, filename_new_queue: &CStr, _filename_orig_queue: Option<&CStr>) {}
 
     fn queue_get(&mut self, filename: &CStr) -> bool {
@@ -62,12 +138,44 @@ pub trait CustomMutator {
         None
     }
 
-    /*fn post_process(&self, buffer: &[u8], unsigned char **out_buf)-> usize;
-    int afl_custom_init_trim(&self, buffer: &[u8]);
-    size_t afl_custom_trim(&self, unsigned char **out_buf);
-    int afl_custom_post_trim(&self, unsigned char success);
-    size_t afl_custom_havoc_mutation(&self, buffer: &[u8], unsigned char **out_buf, size_t max_size);
-    unsigned char afl_custom_havoc_mutation_probability(&self);*/
+    /// Initializes trimming for the given `buffer` and returns the number of trim steps AFL++ should attempt.
+    /// AFL++ will then alternate calls to [`RawCustomMutator::trim`] and [`RawCustomMutator::post_trim`] that many times at most.
+    /// Returning `0` (the default) tells AFL++ that this mutator does not support trimming.
+    /// Overriding this only takes effect once you export with `export_mutator!(raw MyMutator, trim)`: AFL++ decides
+    /// whether to use its own built-in trimming based on whether the `afl_custom_trim` symbol is *present* at all,
+    /// so [`export_mutator`](crate::export_mutator) only emits the trim symbols when explicitly asked to.
+    fn init_trim(&mut self, buffer: &[u8]) -> u32 {
+        0
+    }
+
+    /// Returns the current trimmed candidate buffer. Only called after [`RawCustomMutator::init_trim`] returned a non-zero step count.
+    fn trim(&mut self) -> &[u8] {
+        unreachable!("trim() called without any steps requested from init_trim()")
+    }
+
+    /// Called after AFL++ ran the candidate returned by [`RawCustomMutator::trim`]. `success` indicates whether the trimmed candidate preserved coverage.
+    /// Returns the index of the next trim step.
+    fn post_trim(&mut self, success: bool) -> u32 {
+        0
+    }
+
+    /// Transforms `buffer` into the byte stream that is actually written to the target, right before execution.
+    /// This allows a mutator to keep an internal representation (eg a structured/AST form) during [`RawCustomMutator::fuzz`] and only serialize it here.
+    /// The default implementation passes the buffer through unchanged.
+    fn post_process<'s>(&'s mut self, buffer: &'s [u8]) -> &'s [u8] {
+        buffer
+    }
+
+    /// Injects a single custom mutation into AFL++'s havoc stage. Only called when [`RawCustomMutator::havoc_mutation_probability`] returns non-zero.
+    fn havoc_mutation(&mut self, buffer: &mut [u8], max_size: usize) -> FuzzResult {
+        unreachable!("havoc_mutation() called despite havoc_mutation_probability() returning 0")
+    }
+
+    /// The probability (0-100) that AFL++ calls [`RawCustomMutator::havoc_mutation`] instead of one of its own havoc mutations.
+    /// Returning `0` (the default) means `havoc_mutation` is never called.
+    fn havoc_mutation_probability(&mut self) -> u8 {
+        0
+    }
 }
 
 /// Wrappers for the custom mutator which provide the bridging between the C API and CustomMutator.
@@ -77,22 +185,36 @@ pub mod wrappers {
     use aflpp_custom_mutator_sys::afl_state;
     use core::slice;
     use std::{
+        any::Any,
         convert::TryInto,
-        ffi::{c_void, CStr},
+        ffi::{c_void, CStr, CString, OsStr},
         mem::ManuallyDrop,
         os::raw::{c_char, c_uint},
+        os::unix::ffi::OsStrExt,
+        panic::{catch_unwind, AssertUnwindSafe},
+        path::Path,
         ptr::null,
     };
 
-    use crate::{CustomMutator, FuzzResult};
+    use crate::{CustomMutator, FuzzResult, RawCustomMutator};
+
+    /// Logs a caught panic to stderr. Used to turn a panic at the FFI boundary into a diagnosable message instead of silent undefined behavior.
+    fn log_panic(payload: Box<dyn Any + Send>) {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Box<dyn Any>".to_string());
+        eprintln!("custom mutator panicked, failing fast: {message}");
+    }
 
     /// A structure to be used as the data pointer for our custom mutator. This was used as additional storage and is kept for now in case its needed later.
     /// Also has some convenience functions for FFI conversions (from and to ptr) and tries to make misuse hard (see [`FFIContext::from`]).
-    struct FFIContext<M: CustomMutator> {
+    struct FFIContext<M: RawCustomMutator> {
         mutator: M,
     }
 
-    impl<M: CustomMutator> FFIContext<M> {
+    impl<M: RawCustomMutator> FFIContext<M> {
         fn from(ptr: *mut c_void) -> ManuallyDrop<Box<Self>> {
             assert!(!ptr.is_null());
             ManuallyDrop::new(unsafe { Box::from_raw(ptr as *mut Self) })
@@ -109,17 +231,111 @@ pub mod wrappers {
         }
     }
 
+    /// Adapts the ergonomic [`CustomMutator`] (`&str`/`&Path`-based) to the [`RawCustomMutator`] (`&CStr`-based) API
+    /// that the rest of this module and [`export_mutator`](crate::export_mutator) operate on.
+    /// `describe`/`introspection` need a NUL-terminated buffer to hand back as a `&CStr`; this struct owns that
+    /// buffer (re-creating it on every call) so the returned pointer stays valid until the next invocation.
+    #[doc(hidden)]
+    pub struct CustomMutatorAdapter<M> {
+        inner: M,
+        describe_buf: Option<CString>,
+        introspection_buf: Option<CString>,
+    }
+
+    impl<M: CustomMutator> RawCustomMutator for CustomMutatorAdapter<M> {
+        fn init(afl: &'static afl_state, seed: c_uint) -> Self {
+            Self {
+                inner: M::init(afl, seed),
+                describe_buf: None,
+                introspection_buf: None,
+            }
+        }
+
+        fn fuzz(
+            &mut self,
+            buffer: &mut [u8],
+            add_buff: Option<&[u8]>,
+            max_size: usize,
+        ) -> FuzzResult {
+            self.inner.fuzz(buffer, add_buff, max_size)
+        }
+
+        fn fuzz_count(&mut self, buffer: &[u8]) -> u32 {
+            self.inner.fuzz_count(buffer)
+        }
+
+        fn queue_new_entry(&mut self, filename_new_queue: &CStr, filename_orig_queue: Option<&CStr>) {
+            let filename_new_queue = Path::new(OsStr::from_bytes(filename_new_queue.to_bytes()));
+            let filename_orig_queue = filename_orig_queue
+                .map(|f| Path::new(OsStr::from_bytes(f.to_bytes())));
+            self.inner
+                .queue_new_entry(filename_new_queue, filename_orig_queue)
+        }
+
+        fn queue_get(&mut self, filename: &CStr) -> bool {
+            self.inner
+                .queue_get(Path::new(OsStr::from_bytes(filename.to_bytes())))
+        }
+
+        fn describe(&mut self, max_description: usize) -> Option<&CStr> {
+            let description = self.inner.describe(max_description)?;
+            self.describe_buf = Some(
+                CString::new(description).expect("describe() returned a string containing a NUL byte"),
+            );
+            self.describe_buf.as_deref()
+        }
+
+        fn introspection(&mut self) -> Option<&CStr> {
+            let introspection = self.inner.introspection()?;
+            self.introspection_buf = Some(
+                CString::new(introspection)
+                    .expect("introspection() returned a string containing a NUL byte"),
+            );
+            self.introspection_buf.as_deref()
+        }
+
+        fn init_trim(&mut self, buffer: &[u8]) -> u32 {
+            self.inner.init_trim(buffer)
+        }
+
+        fn trim(&mut self) -> &[u8] {
+            self.inner.trim()
+        }
+
+        fn post_trim(&mut self, success: bool) -> u32 {
+            self.inner.post_trim(success)
+        }
+
+        fn post_process<'s>(&'s mut self, buffer: &'s [u8]) -> &'s [u8] {
+            self.inner.post_process(buffer)
+        }
+
+        fn havoc_mutation(&mut self, buffer: &mut [u8], max_size: usize) -> FuzzResult {
+            self.inner.havoc_mutation(buffer, max_size)
+        }
+
+        fn havoc_mutation_probability(&mut self) -> u8 {
+            self.inner.havoc_mutation_probability()
+        }
+    }
+
     /// Internal function used in the macro
-    pub fn afl_custom_init_<M: CustomMutator>(
+    pub fn afl_custom_init_<M: RawCustomMutator>(
         afl: Option<&'static afl_state>,
         seed: c_uint,
     ) -> *const c_void {
-        let afl = afl.expect("mutator func called with NULL afl");
-        FFIContext::<M>::new(afl, seed).into_ptr()
+        catch_unwind(AssertUnwindSafe(|| {
+            let afl = afl.expect("mutator func called with NULL afl");
+            FFIContext::<M>::new(afl, seed).into_ptr()
+        }))
+        .unwrap_or_else(|payload| {
+            log_panic(payload);
+            null()
+        })
     }
 
     /// Internal function used in the macro
-    pub unsafe fn afl_custom_fuzz_<M: CustomMutator>(
+    pub unsafe fn afl_custom_fuzz_<M: RawCustomMutator>(
         data: *mut c_void,
         buf: *mut u8,
         buf_size: usize,
@@ -128,121 +344,294 @@ pub mod wrappers {
         add_buf_size: usize,
         max_size: usize,
     ) -> usize {
-        let mut context = FFIContext::<M>::from(data);
-        if buf.is_null() {
-            panic!("null buf passed to afl_custom_fuzz")
-        }
-        if out_buf.is_null() {
-            panic!("null out_buf passed to afl_custom_fuzz")
-        }
-        let buff_slice = slice::from_raw_parts_mut(buf, buf_size);
-        let add_buff_slice = if add_buf.is_null() {
-            None
-        } else {
-            Some(slice::from_raw_parts(add_buf, add_buf_size))
-        };
-        match context
-            .mutator
-            .fuzz(buff_slice, add_buff_slice, max_size.try_into().unwrap())
-        {
-            FuzzResult::InPlace => {
-                *out_buf = buff_slice.as_ptr();
-                buff_slice.len().try_into().unwrap()
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let mut context = FFIContext::<M>::from(data);
+            if buf.is_null() {
+                panic!("null buf passed to afl_custom_fuzz")
             }
-            FuzzResult::NewBuffer(b) => {
-                *out_buf = b.as_ptr();
-                b.len().try_into().unwrap()
+            if out_buf.is_null() {
+                panic!("null out_buf passed to afl_custom_fuzz")
             }
-            FuzzResult::Fail => {
-                *out_buf = null();
-                0
+            let buff_slice = slice::from_raw_parts_mut(buf, buf_size);
+            let add_buff_slice = if add_buf.is_null() {
+                None
+            } else {
+                Some(slice::from_raw_parts(add_buf, add_buf_size))
+            };
+            match context
+                .mutator
+                .fuzz(buff_slice, add_buff_slice, max_size.try_into().unwrap())
+            {
+                FuzzResult::InPlace => (buff_slice.as_ptr(), buff_slice.len().try_into().unwrap()),
+                FuzzResult::NewBuffer(b) => (b.as_ptr(), b.len().try_into().unwrap()),
+                FuzzResult::Fail => (null(), 0),
             }
+        }));
+        let (ptr, len) = result.unwrap_or_else(|payload| {
+            log_panic(payload);
+            (null(), 0)
+        });
+        if !out_buf.is_null() {
+            *out_buf = ptr;
         }
+        len
     }
 
     /// Internal function used in the macro
-    pub unsafe fn afl_custom_fuzz_count_<M: CustomMutator>(
+    pub unsafe fn afl_custom_fuzz_count_<M: RawCustomMutator>(
         data: *mut c_void,
         buf: *const u8,
         buf_size: usize,
     ) -> u32 {
-        let mut context = FFIContext::<M>::from(data);
-        if buf.is_null() {
-            panic!("null buf passed to afl_custom_fuzz")
-        }
-        let buf_slice = slice::from_raw_parts(buf, buf_size);
-        // see https://doc.rust-lang.org/nomicon/borrow-splitting.html
-        let ctx = &mut **context;
-        let mutator = &mut ctx.mutator;
-        mutator.fuzz_count(buf_slice)
+        catch_unwind(AssertUnwindSafe(|| {
+            let mut context = FFIContext::<M>::from(data);
+            if buf.is_null() {
+                panic!("null buf passed to afl_custom_fuzz")
+            }
+            let buf_slice = slice::from_raw_parts(buf, buf_size);
+            // see https://doc.rust-lang.org/nomicon/borrow-splitting.html
+            let ctx = &mut **context;
+            let mutator = &mut ctx.mutator;
+            mutator.fuzz_count(buf_slice)
+        }))
+        .unwrap_or_else(|payload| {
+            log_panic(payload);
+            0
+        })
     }
 
     /// Internal function used in the macro
-    pub fn afl_custom_queue_new_entry_<M: CustomMutator>(
+    pub fn afl_custom_queue_new_entry_<M: RawCustomMutator>(
         data: *mut c_void,
         filename_new_queue: *const c_char,
         filename_orig_queue: *const c_char,
     ) {
-        let mut context = FFIContext::<M>::from(data);
-        if filename_new_queue.is_null() {
-            panic!("received null filename_new_queue in afl_custom_queue_new_entry");
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let mut context = FFIContext::<M>::from(data);
+            if filename_new_queue.is_null() {
+                panic!("received null filename_new_queue in afl_custom_queue_new_entry");
+            }
+            let filename_new_queue = unsafe { CStr::from_ptr(filename_new_queue) };
+            let filename_orig_queue = if !filename_orig_queue.is_null() {
+                Some(unsafe { CStr::from_ptr(filename_orig_queue) })
+            } else {
+                None
+            };
+            context
+                .mutator
+                .queue_new_entry(filename_new_queue, filename_orig_queue);
+        }));
+        if let Err(payload) = result {
+            log_panic(payload);
         }
-        let filename_new_queue = unsafe { CStr::from_ptr(filename_new_queue) };
-        let filename_orig_queue = if !filename_orig_queue.is_null() {
-            Some(unsafe { CStr::from_ptr(filename_orig_queue) })
-        } else {
-            None
-        };
-        context
-            .mutator
-            .queue_new_entry(filename_new_queue, filename_orig_queue);
     }
 
     /// Internal function used in the macro
-    pub unsafe fn afl_custom_deinit_<M: CustomMutator>(data: *mut c_void) {
-        // drop the context
-        ManuallyDrop::into_inner(FFIContext::<M>::from(data));
+    pub unsafe fn afl_custom_deinit_<M: RawCustomMutator>(data: *mut c_void) {
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            // drop the context
+            ManuallyDrop::into_inner(FFIContext::<M>::from(data));
+        }));
+        if let Err(payload) = result {
+            log_panic(payload);
+        }
     }
 
     /// Internal function used in the macro
-    pub fn afl_custom_introspection_<M: CustomMutator>(data: *mut c_void) -> *const c_char {
-        let mut context = FFIContext::<M>::from(data);
-        if let Some(res) = context.mutator.introspection() {
-            res.as_ptr()
-        } else {
+    pub fn afl_custom_introspection_<M: RawCustomMutator>(data: *mut c_void) -> *const c_char {
+        catch_unwind(AssertUnwindSafe(|| {
+            let mut context = FFIContext::<M>::from(data);
+            if let Some(res) = context.mutator.introspection() {
+                res.as_ptr()
+            } else {
+                null()
+            }
+        }))
+        .unwrap_or_else(|payload| {
+            log_panic(payload);
             null()
-        }
+        })
     }
 
     /// Internal function used in the macro
-    pub fn afl_custom_describe_<M: CustomMutator>(
+    pub fn afl_custom_describe_<M: RawCustomMutator>(
         data: *mut c_void,
         max_description_len: usize,
     ) -> *const c_char {
-        let mut context = FFIContext::<M>::from(data);
-        if let Some(res) = context.mutator.describe(max_description_len) {
-            res.as_ptr()
-        } else {
+        catch_unwind(AssertUnwindSafe(|| {
+            let mut context = FFIContext::<M>::from(data);
+            if let Some(res) = context.mutator.describe(max_description_len) {
+                res.as_ptr()
+            } else {
+                null()
+            }
+        }))
+        .unwrap_or_else(|payload| {
+            log_panic(payload);
             null()
-        }
+        })
     }
 
     /// Internal function used in the macro
-    pub fn afl_custom_queue_get_<M: CustomMutator>(
+    pub fn afl_custom_queue_get_<M: RawCustomMutator>(
         data: *mut c_void,
         filename: *const c_char,
     ) -> u8 {
-        let mut context = FFIContext::<M>::from(data);
-        assert!(!filename.is_null());
+        catch_unwind(AssertUnwindSafe(|| {
+            let mut context = FFIContext::<M>::from(data);
+            assert!(!filename.is_null());
+
+            context
+                .mutator
+                .queue_get(unsafe { CStr::from_ptr(filename) }) as u8
+        }))
+        .unwrap_or_else(|payload| {
+            log_panic(payload);
+            false as u8
+        })
+    }
 
-        context
-            .mutator
-            .queue_get(unsafe { CStr::from_ptr(filename) }) as u8
+    /// Internal function used in the macro
+    pub unsafe fn afl_custom_init_trim_<M: RawCustomMutator>(
+        data: *mut c_void,
+        buf: *const u8,
+        buf_size: usize,
+    ) -> u32 {
+        catch_unwind(AssertUnwindSafe(|| {
+            let mut context = FFIContext::<M>::from(data);
+            if buf.is_null() {
+                panic!("null buf passed to afl_custom_init_trim")
+            }
+            let buf_slice = slice::from_raw_parts(buf, buf_size);
+            context.mutator.init_trim(buf_slice)
+        }))
+        .unwrap_or_else(|payload| {
+            log_panic(payload);
+            0
+        })
+    }
+
+    /// Internal function used in the macro
+    pub unsafe fn afl_custom_trim_<M: RawCustomMutator>(
+        data: *mut c_void,
+        out_buf: *mut *const u8,
+    ) -> usize {
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let mut context = FFIContext::<M>::from(data);
+            if out_buf.is_null() {
+                panic!("null out_buf passed to afl_custom_trim")
+            }
+            let trimmed = context.mutator.trim();
+            (trimmed.as_ptr(), trimmed.len())
+        }));
+        let (ptr, len) = result.unwrap_or_else(|payload| {
+            log_panic(payload);
+            (null(), 0)
+        });
+        if !out_buf.is_null() {
+            *out_buf = ptr;
+        }
+        len
+    }
+
+    /// Internal function used in the macro
+    pub fn afl_custom_post_trim_<M: RawCustomMutator>(data: *mut c_void, success: u8) -> u32 {
+        catch_unwind(AssertUnwindSafe(|| {
+            let mut context = FFIContext::<M>::from(data);
+            context.mutator.post_trim(success != 0)
+        }))
+        .unwrap_or_else(|payload| {
+            log_panic(payload);
+            0
+        })
+    }
+
+    /// Internal function used in the macro
+    pub unsafe fn afl_custom_post_process_<M: RawCustomMutator>(
+        data: *mut c_void,
+        buf: *mut u8,
+        buf_size: usize,
+        out_buf: *mut *const u8,
+    ) -> usize {
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let mut context = FFIContext::<M>::from(data);
+            if buf.is_null() {
+                panic!("null buf passed to afl_custom_post_process")
+            }
+            if out_buf.is_null() {
+                panic!("null out_buf passed to afl_custom_post_process")
+            }
+            let buf_slice = slice::from_raw_parts(buf, buf_size);
+            let processed = context.mutator.post_process(buf_slice);
+            (processed.as_ptr(), processed.len())
+        }));
+        let (ptr, len) = result.unwrap_or_else(|payload| {
+            log_panic(payload);
+            (null(), 0)
+        });
+        if !out_buf.is_null() {
+            *out_buf = ptr;
+        }
+        len
+    }
+
+    /// Internal function used in the macro
+    pub unsafe fn afl_custom_havoc_mutation_<M: RawCustomMutator>(
+        data: *mut c_void,
+        buf: *mut u8,
+        buf_size: usize,
+        out_buf: *mut *const u8,
+        max_size: usize,
+    ) -> usize {
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let mut context = FFIContext::<M>::from(data);
+            if buf.is_null() {
+                panic!("null buf passed to afl_custom_havoc_mutation")
+            }
+            if out_buf.is_null() {
+                panic!("null out_buf passed to afl_custom_havoc_mutation")
+            }
+            let buf_slice = slice::from_raw_parts_mut(buf, buf_size);
+            match context.mutator.havoc_mutation(buf_slice, max_size) {
+                FuzzResult::InPlace => (buf_slice.as_ptr(), buf_slice.len()),
+                FuzzResult::NewBuffer(b) => (b.as_ptr(), b.len()),
+                FuzzResult::Fail => (null(), 0),
+            }
+        }));
+        let (ptr, len) = result.unwrap_or_else(|payload| {
+            log_panic(payload);
+            (null(), 0)
+        });
+        if !out_buf.is_null() {
+            *out_buf = ptr;
+        }
+        len
+    }
+
+    /// Internal function used in the macro
+    pub fn afl_custom_havoc_mutation_probability_<M: RawCustomMutator>(data: *mut c_void) -> u8 {
+        catch_unwind(AssertUnwindSafe(|| {
+            let mut context = FFIContext::<M>::from(data);
+            context.mutator.havoc_mutation_probability()
+        }))
+        .unwrap_or_else(|payload| {
+            log_panic(payload);
+            0
+        })
     }
 }
 
 /// exports the given Mutator as a custom mutator as the C interface that AFL++ expects.
 /// It is not possible to call this macro multiple times, because it would define the custom mutator symbols multiple times.
+/// Accepts a type implementing [`CustomMutator`], the ergonomic `&str`/`&Path`-based trait.
+/// For a type implementing the lower-level [`RawCustomMutator`] instead, use `export_mutator!(raw MyMutator)`.
+///
+/// AFL++ dispatches trimming by symbol *presence*: once a custom mutator exports `afl_custom_trim`, AFL++ routes
+/// every queue entry through custom trimming instead of its own built-in deterministic trimming, even if
+/// [`CustomMutator::init_trim`] returns `0` steps for that entry. Because of that, this macro does **not** export
+/// `afl_custom_init_trim`/`afl_custom_trim`/`afl_custom_post_trim` unless asked to: add a trailing `, trim` to opt
+/// in once your mutator actually implements [`CustomMutator::init_trim`]/[`CustomMutator::trim`]/[`CustomMutator::post_trim`],
+/// eg `export_mutator!(MyMutator, trim)` or `export_mutator!(raw MyMutator, trim)`.
 /// # Example
 /// ```
 /// # #[macro_use] extern crate aflpp_custom_mutator;
@@ -258,7 +647,21 @@ pub mod wrappers {
 /// ```
 #[macro_export]
 macro_rules! export_mutator {
+    (raw $mutator_type:ty, trim) => {
+        $crate::export_mutator!(@impl $mutator_type);
+        $crate::export_mutator!(@trim $mutator_type);
+    };
+    (raw $mutator_type:ty) => {
+        $crate::export_mutator!(@impl $mutator_type);
+    };
+    ($mutator_type:ty, trim) => {
+        $crate::export_mutator!(@impl $crate::wrappers::CustomMutatorAdapter<$mutator_type>);
+        $crate::export_mutator!(@trim $crate::wrappers::CustomMutatorAdapter<$mutator_type>);
+    };
     ($mutator_type:ty) => {
+        $crate::export_mutator!(@impl $crate::wrappers::CustomMutatorAdapter<$mutator_type>);
+    };
+    (@impl $mutator_type:ty) => {
         #[no_mangle]
         pub extern "C" fn afl_custom_init(
             afl: ::std::option::Option<&'static $crate::afl_state>,
@@ -342,6 +745,68 @@ macro_rules! export_mutator {
         pub extern "C" fn afl_custom_deinit(data: *mut ::std::os::raw::c_void) {
             unsafe { $crate::wrappers::afl_custom_deinit_::<$mutator_type>(data) }
         }
+
+        #[no_mangle]
+        pub extern "C" fn afl_custom_post_process(
+            data: *mut ::std::os::raw::c_void,
+            buf: *mut u8,
+            buf_size: usize,
+            out_buf: *mut *const u8,
+        ) -> usize {
+            unsafe {
+                $crate::wrappers::afl_custom_post_process_::<$mutator_type>(
+                    data, buf, buf_size, out_buf,
+                )
+            }
+        }
+
+        #[no_mangle]
+        pub extern "C" fn afl_custom_havoc_mutation(
+            data: *mut ::std::os::raw::c_void,
+            buf: *mut u8,
+            buf_size: usize,
+            out_buf: *mut *const u8,
+            max_size: usize,
+        ) -> usize {
+            unsafe {
+                $crate::wrappers::afl_custom_havoc_mutation_::<$mutator_type>(
+                    data, buf, buf_size, out_buf, max_size,
+                )
+            }
+        }
+
+        #[no_mangle]
+        pub extern "C" fn afl_custom_havoc_mutation_probability(
+            data: *mut ::std::os::raw::c_void,
+        ) -> u8 {
+            $crate::wrappers::afl_custom_havoc_mutation_probability_::<$mutator_type>(data)
+        }
+    };
+    (@trim $mutator_type:ty) => {
+        #[no_mangle]
+        pub extern "C" fn afl_custom_init_trim(
+            data: *mut ::std::os::raw::c_void,
+            buf: *const u8,
+            buf_size: usize,
+        ) -> u32 {
+            unsafe { $crate::wrappers::afl_custom_init_trim_::<$mutator_type>(data, buf, buf_size) }
+        }
+
+        #[no_mangle]
+        pub extern "C" fn afl_custom_trim(
+            data: *mut ::std::os::raw::c_void,
+            out_buf: *mut *const u8,
+        ) -> usize {
+            unsafe { $crate::wrappers::afl_custom_trim_::<$mutator_type>(data, out_buf) }
+        }
+
+        #[no_mangle]
+        pub extern "C" fn afl_custom_post_trim(
+            data: *mut ::std::os::raw::c_void,
+            success: u8,
+        ) -> u32 {
+            $crate::wrappers::afl_custom_post_trim_::<$mutator_type>(data, success)
+        }
     };
 }
 